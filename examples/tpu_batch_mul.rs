@@ -0,0 +1,85 @@
+//! Benchmark comparing batched vs looped matmul dispatch for multiplying
+//! many polynomial pairs at once, across the same degree sweep used by
+//! `tpu_matrix_mul`.
+
+use candle_core::{Device, Result as CandleResult};
+use rns_tpu::core::{multiply_batch_with_device, Polynomial};
+use std::time::Instant;
+
+fn main() -> CandleResult<()> {
+    println!("=== TPU Batched GEMM Benchmark ===\n");
+
+    let device = Device::new_metal(0)?;
+    let batch_size = 8;
+    let degrees = [512, 1024, 2048, 4096, 8192];
+    let iterations = 5;
+
+    // Correctness check on a small batch.
+    let small_a: Vec<Polynomial> = (0..batch_size)
+        .map(|k| Polynomial::new(vec![1 + k as u64, 2, 3, 4]))
+        .collect();
+    let small_b: Vec<Polynomial> = (0..batch_size)
+        .map(|k| Polynomial::new(vec![5 + k as u64, 6, 7]))
+        .collect();
+
+    let batched_result = multiply_batch_with_device(&small_a, &small_b, &device)?;
+    let naive_results: Vec<Polynomial> = small_a
+        .iter()
+        .zip(small_b.iter())
+        .map(|(a, b)| a.multiply_naive(b))
+        .collect();
+    let all_match = batched_result
+        .iter()
+        .zip(naive_results.iter())
+        .all(|(batched, naive)| batched.approx_eq(naive, 0.1));
+    println!("Batched results match naive: {}\n", all_match);
+
+    println!("=== Batched vs Looped Dispatch ===\n");
+    for deg in degrees {
+        let batch_a: Vec<Polynomial> = (0..batch_size)
+            .map(|k| {
+                Polynomial::new((0..deg).map(|x| ((x + k) as u64 % 1000) + 1).collect())
+            })
+            .collect();
+        let batch_b: Vec<Polynomial> = (0..batch_size)
+            .map(|k| {
+                Polynomial::new((0..deg).map(|x| ((x + k) as u64 % 1000) + 1).collect())
+            })
+            .collect();
+
+        println!(
+            "Polynomials: degree {} each, batch of {} (averaged over {} iterations)",
+            deg, batch_size, iterations
+        );
+
+        // Warm-up
+        for (a, b) in batch_a.iter().zip(batch_b.iter()) {
+            let _ = a.multiply_matrix_with_device(b, &device)?;
+        }
+        let _ = multiply_batch_with_device(&batch_a, &batch_b, &device)?;
+
+        // Looped dispatch: one matmul per pair.
+        let start = Instant::now();
+        for _ in 0..iterations {
+            for (a, b) in batch_a.iter().zip(batch_b.iter()) {
+                let _ = a.multiply_matrix_with_device(b, &device)?;
+            }
+        }
+        let looped_time = start.elapsed() / iterations as u32;
+        println!("  Looped time:  {:?}", looped_time);
+
+        // Batched dispatch: one matmul for the whole batch.
+        let start = Instant::now();
+        for _ in 0..iterations {
+            let _ = multiply_batch_with_device(&batch_a, &batch_b, &device)?;
+        }
+        let batched_time = start.elapsed() / iterations as u32;
+        println!("  Batched time: {:?}", batched_time);
+
+        let speedup = looped_time.as_secs_f64() / batched_time.as_secs_f64();
+        println!("  Speedup: {:.2}x", speedup);
+        println!();
+    }
+
+    Ok(())
+}
@@ -1,109 +1,11 @@
 //! Example demonstrating polynomial multiplication via TPU acceleration
 //! using candle-coreml on Apple Silicon
 
-use candle_core::{Device, Result as CandleResult, Tensor};
+use candle_core::{Device, Result as CandleResult};
+use rns_tpu::core::ntt::NTT_PRIME;
+use rns_tpu::core::{ModInt, NttContext, Polynomial, RnsContext};
 use std::time::Instant;
 
-/// Simple polynomial with u64 coefficients
-#[derive(Debug, Clone)]
-struct Polynomial {
-    coefficients: Vec<u64>,
-}
-
-impl Polynomial {
-    /// Create a new polynomial from coefficients (lowest degree first)
-    fn new(coefficients: Vec<u64>) -> Self {
-        Polynomial { coefficients }
-    }
-
-    /// Naive O(n²) polynomial multiplication
-    fn multiply_naive(&self, other: &Polynomial) -> Polynomial {
-        let n = self.coefficients.len();
-        let m = other.coefficients.len();
-        let mut result = vec![0u64; n + m - 1];
-
-        for (i, &a) in self.coefficients.iter().enumerate() {
-            for (j, &b) in other.coefficients.iter().enumerate() {
-                result[i + j] += a * b;
-            }
-        }
-
-        Polynomial::new(result)
-    }
-
-    /// Convert polynomial to Toeplitz matrix representation for multiplication
-    /// Matrix M is Toeplitz where M_{i,j} = a_{i-j} for i >= j, 0 otherwise
-    /// When multiplied by vector B, gives A × B
-    fn to_multiplication_matrix(&self, result_degree: usize) -> Vec<Vec<u64>> {
-        let n = self.coefficients.len();
-        let mut matrix = vec![vec![0u64; n]; result_degree];
-
-        for i in 0..result_degree {
-            for j in 0..n {
-                if i >= j && (i - j) < n {
-                    matrix[i][j] = self.coefficients[i - j];
-                }
-            }
-        }
-
-        matrix
-    }
-
-    /// Multiply using matrix multiplication via candle-coreml with provided device
-    fn multiply_matrix_with_device(
-        &self,
-        other: &Polynomial,
-        device: &Device,
-    ) -> CandleResult<Polynomial> {
-        let n = self.coefficients.len();
-        let m = other.coefficients.len();
-        let result_degree = n + m - 1;
-
-        // Build multiplication matrix (Toeplitz: result_degree x n)
-        let matrix = self.to_multiplication_matrix(result_degree);
-        let matrix_flat: Vec<f32> = matrix
-            .iter()
-            .flat_map(|row| row.iter().map(|&x| x as f32))
-            .collect();
-
-        let matrix_tensor =
-            Tensor::from_vec(matrix_flat, (result_degree, n), device)?;
-
-        // Convert other polynomial to vector (n x 1) - need to pad
-        let mut vec_b = vec![0.0f32; n];
-        for (i, &coeff) in other.coefficients.iter().enumerate() {
-            if i < n {
-                vec_b[i] = coeff as f32;
-            }
-        }
-        let b_tensor = Tensor::from_vec(vec_b, (n, 1), device)?;
-
-        // Perform matrix multiplication
-        let result_tensor = matrix_tensor.matmul(&b_tensor)?;
-
-        // Extract result back to polynomial (reshape from 2D to 1D)
-        let result_reshaped = result_tensor.reshape((result_degree,))?;
-        let result_vec = result_reshaped.to_vec1::<f32>()?;
-        let coefficients: Vec<u64> =
-            result_vec.iter().map(|&x| x as u64).collect();
-
-        Ok(Polynomial::new(coefficients))
-    }
-
-    /// Compare if two polynomials are approximately equal
-    fn approx_eq(&self, other: &Polynomial, epsilon: f64) -> bool {
-        let max_len = self.coefficients.len().max(other.coefficients.len());
-        for i in 0..max_len {
-            let a = self.coefficients.get(i).copied().unwrap_or(0);
-            let b = other.coefficients.get(i).copied().unwrap_or(0);
-            if (a as f64 - b as f64).abs() > epsilon {
-                return false;
-            }
-        }
-        true
-    }
-}
-
 fn main() -> CandleResult<()> {
     println!("=== TPU Polynomial Multiplication Example ===\n");
 
@@ -137,6 +39,81 @@ fn main() -> CandleResult<()> {
         result_naive.approx_eq(&result_matrix, 0.1)
     );
 
+    // The raw f32 matrix path above casts u64 coefficients straight to f32,
+    // so it silently loses precision once a partial sum exceeds 2^24. The RNS
+    // path stays exact by multiplying one residue channel per prime and
+    // reconstructing via CRT.
+    println!("\n=== RNS-Exact Multiplication ===\n");
+
+    let big_poly_a =
+        Polynomial::new((0..4096).map(|x| (x as u64 % 1000) + 1).collect());
+    let big_poly_b =
+        Polynomial::new((0..4096).map(|x| (x as u64 % 1000) + 1).collect());
+
+    // Primes must stay under `rns::MAX_RNS_PRIME` so a single residue
+    // product is still f32-exact; pick just enough of them that their
+    // product exceeds the largest possible unreduced convolution term.
+    let bound = 4096u128 * 1000 * 1000;
+    let rns = RnsContext::new(rns_tpu::core::rns::choose_primes_for_bound(bound));
+
+    let expected = big_poly_a.multiply_naive(&big_poly_b);
+    let result_rns = rns.multiply_with_device(&big_poly_a, &big_poly_b, &device)?;
+    let result_lossy = big_poly_a.multiply_matrix_with_device(&big_poly_b, &device)?;
+
+    println!(
+        "RNS result matches naive:   {}",
+        expected.approx_eq(&result_rns, 0.0)
+    );
+    println!(
+        "f32 result matches naive:   {}",
+        expected.approx_eq(&result_lossy, 0.0)
+    );
+
+    // RLWE/FHE schemes multiply in the quotient ring Z_q[x]/(x^n+1), not as
+    // free polynomials, so the wrap-around terms must be negated instead of
+    // simply truncated.
+    println!("\n=== Negacyclic Ring Multiplication ===\n");
+
+    let ring_n = 4;
+    let ring_q = 97;
+    let ring_a = Polynomial::new(vec![1, 2, 3, 4]);
+    let ring_b = Polynomial::new(vec![5, 6, 7, 8]);
+    let ring_result =
+        ring_a.multiply_negacyclic_with_device(&ring_b, ring_n, ring_q, &device)?;
+    println!("A mod (x^4+1): {:?}", ring_a);
+    println!("B mod (x^4+1): {:?}", ring_b);
+    println!("A * B mod (x^4+1, {}): {:?}\n", ring_q, ring_result);
+
+    // The transform itself can run on the TPU too: map the NTT onto dense
+    // matmul instead of paying O(n^2) for the Toeplitz convolution.
+    println!("\n=== NTT-as-Matmul ===\n");
+
+    let ntt = NttContext::new(8, rns_tpu::core::ntt::NTT_PRIME);
+    let ntt_a = Polynomial::new(vec![1, 2, 3, 4]);
+    let ntt_b = Polynomial::new(vec![5, 6, 7, 8]);
+    let ntt_naive = ntt_a.multiply_naive(&ntt_b);
+    let ntt_result = ntt.multiply_ntt_with_device(&ntt_a, &ntt_b, &device)?;
+    println!("Naive (mod-free) result: {:?}", ntt_naive);
+    println!("NTT result (mod q):      {:?}\n", ntt_result);
+
+    // The RNS and NTT routines above reduce residues and compute inverses with
+    // `core::field::ModRing`, the runtime-modulus sibling of ModInt below:
+    // same Barrett-reduction core, but the modulus is chosen at runtime
+    // instead of baked into the type. ModInt is for callers like this one
+    // where the modulus is fixed at compile time.
+    println!("\n=== ModInt Field Arithmetic ===\n");
+
+    let x = ModInt::<NTT_PRIME>::new(123_456_789);
+    let y = ModInt::<NTT_PRIME>::new(987_654_321);
+    println!("x * y mod q = {}", x.mul(y).value());
+    println!("x^-1 * x mod q = {}", x.inv().mul(x).value());
+
+    let values = [x, y, ModInt::<NTT_PRIME>::new(42)];
+    let inverses = ModInt::<NTT_PRIME>::batch_inverse(&values);
+    for (v, v_inv) in values.iter().zip(inverses.iter()) {
+        println!("{} * {}^-1 mod q = {}", v.value(), v.value(), v.mul(*v_inv).value());
+    }
+
     // Larger polynomials for performance comparison
     println!("\n=== Performance Comparison ===\n");
 
@@ -0,0 +1,300 @@
+//! Modular integer field arithmetic, reduced via Barrett reduction.
+//!
+//! [`ModInt<Q>`] takes its modulus as a const generic, for contexts where `Q`
+//! is fixed at compile time. `core::rns` and `core::ntt` pick their prime
+//! moduli at runtime (RNS primes via `rns::choose_primes_for_bound`, NTT
+//! moduli per call), so they use [`ModRing`] instead — same Barrett-reduction
+//! core, modulus stored at construction rather than baked into the type.
+//! Between the two, this is the one tested primitive modular arithmetic
+//! anywhere in `core` should route through instead of ad-hoc `% q` or a
+//! hand-rolled extended-Euclid inverse.
+
+/// An element of `Z_Q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModInt<const Q: u64> {
+    value: u64,
+}
+
+impl<const Q: u64> ModInt<Q> {
+    /// Barrett reduction constant `mu = floor(2^64 / Q)`, precomputed once
+    /// per modulus so `reduce` avoids a division on every call.
+    const MU: u128 = (1u128 << 64) / Q as u128;
+
+    /// Wrap a raw integer, reducing it into `[0, Q)`.
+    pub fn new(value: u64) -> Self {
+        ModInt {
+            value: value % Q,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    /// Barrett-reduce a product `x < Q^2` down to `[0, Q)`:
+    /// `x - floor((x*mu) >> 64) * Q`, with one conditional subtraction.
+    fn reduce(x: u128) -> u64 {
+        let quotient_estimate = (x * Self::MU) >> 64;
+        let mut r = (x - quotient_estimate * Q as u128) as u64;
+        if r >= Q {
+            r -= Q;
+        }
+        r
+    }
+
+    pub fn add(self, other: Self) -> Self {
+        let sum = self.value + other.value;
+        ModInt {
+            value: if sum >= Q { sum - Q } else { sum },
+        }
+    }
+
+    pub fn sub(self, other: Self) -> Self {
+        let value = if self.value >= other.value {
+            self.value - other.value
+        } else {
+            self.value + Q - other.value
+        };
+        ModInt { value }
+    }
+
+    pub fn mul(self, other: Self) -> Self {
+        ModInt {
+            value: Self::reduce(self.value as u128 * other.value as u128),
+        }
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = ModInt::new(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(base);
+            }
+            base = base.mul(base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Multiplicative inverse via Fermat's little theorem (`Q` must be prime
+    /// and `self` non-zero — zero has no inverse). Checked with a hard
+    /// `assert`, not `debug_assert`: this crate's Metal-backed benchmarks run
+    /// in release, where a silently wrong `0` inverse would be worse than a
+    /// panic.
+    pub fn inv(self) -> Self {
+        assert_ne!(self.value, 0, "0 has no multiplicative inverse mod Q");
+        self.pow(Q - 2)
+    }
+
+    /// Invert every element of `values` with a single costly `inv`, using
+    /// the product trick: accumulate running products, invert the total
+    /// once, then walk backwards dividing it back out. Every element must be
+    /// non-zero: a zero element makes the running product (and therefore
+    /// every inverse derived from it) zero. Checked up front with a hard
+    /// `assert`, for the same reason as [`Self::inv`].
+    pub fn batch_inverse(values: &[Self]) -> Vec<Self> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+        assert!(
+            values.iter().all(|v| v.value != 0),
+            "batch_inverse requires every element to be non-zero"
+        );
+
+        let mut prefix = Vec::with_capacity(values.len());
+        let mut running = ModInt::new(1);
+        for &v in values {
+            running = running.mul(v);
+            prefix.push(running);
+        }
+
+        let mut inv_running = prefix[values.len() - 1].inv();
+        let mut result = vec![ModInt::new(0); values.len()];
+        for i in (0..values.len()).rev() {
+            let prefix_before = if i == 0 { ModInt::new(1) } else { prefix[i - 1] };
+            result[i] = inv_running.mul(prefix_before);
+            inv_running = inv_running.mul(values[i]);
+        }
+
+        result
+    }
+}
+
+/// Runtime-modulus counterpart to [`ModInt<Q>`]: same Barrett-reduction core,
+/// but the modulus is a constructor argument instead of a const generic, for
+/// callers that choose it at runtime (`core::rns`'s RNS primes, `core::ntt`'s
+/// per-transform NTT modulus). One `ModRing` is built per modulus so `mu` is
+/// precomputed once and reused across every reduction under it.
+#[derive(Debug, Clone, Copy)]
+pub struct ModRing {
+    modulus: u64,
+    mu: u128,
+}
+
+impl ModRing {
+    /// Build a ring over `modulus`, precomputing the Barrett constant
+    /// `mu = floor(2^64 / modulus)` once.
+    pub fn new(modulus: u64) -> Self {
+        ModRing {
+            modulus,
+            mu: (1u128 << 64) / modulus as u128,
+        }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Reduce an arbitrary `u64` into `[0, modulus)`.
+    pub fn reduce_u64(&self, x: u64) -> u64 {
+        x % self.modulus
+    }
+
+    /// Barrett-reduce a product `x < modulus^2` down to `[0, modulus)`.
+    fn reduce(&self, x: u128) -> u64 {
+        let quotient_estimate = (x * self.mu) >> 64;
+        let mut r = (x - quotient_estimate * self.modulus as u128) as u64;
+        if r >= self.modulus {
+            r -= self.modulus;
+        }
+        r
+    }
+
+    pub fn add(&self, a: u64, b: u64) -> u64 {
+        let sum = a + b;
+        if sum >= self.modulus {
+            sum - self.modulus
+        } else {
+            sum
+        }
+    }
+
+    pub fn sub(&self, a: u64, b: u64) -> u64 {
+        if a >= b {
+            a - b
+        } else {
+            a + self.modulus - b
+        }
+    }
+
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    pub fn pow(&self, base: u64, mut exp: u64) -> u64 {
+        let mut base = base % self.modulus;
+        let mut result = 1u64 % self.modulus;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// Multiplicative inverse via Fermat's little theorem (`modulus` must be
+    /// prime and `a` non-zero — zero has no inverse).
+    pub fn inv(&self, a: u64) -> u64 {
+        assert_ne!(
+            a % self.modulus,
+            0,
+            "0 has no multiplicative inverse mod a prime modulus"
+        );
+        self.pow(a, self.modulus - 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q: u64 = 97;
+
+    #[test]
+    fn add_sub_mul_wrap_around_q() {
+        let a = ModInt::<Q>::new(90);
+        let b = ModInt::<Q>::new(20);
+        assert_eq!(a.add(b).value(), (90 + 20) % Q);
+        assert_eq!(b.sub(a).value(), (20 + Q - 90) % Q);
+        assert_eq!(a.mul(b).value(), (90 * 20) % Q);
+    }
+
+    #[test]
+    fn pow_matches_repeated_multiplication() {
+        let a = ModInt::<Q>::new(5);
+        let mut expected = ModInt::<Q>::new(1);
+        for _ in 0..13 {
+            expected = expected.mul(a);
+        }
+        assert_eq!(a.pow(13).value(), expected.value());
+    }
+
+    #[test]
+    fn inv_is_a_true_multiplicative_inverse() {
+        for v in 1..Q {
+            let x = ModInt::<Q>::new(v);
+            assert_eq!(x.inv().mul(x).value(), 1, "failed for {v}");
+        }
+    }
+
+    #[test]
+    fn batch_inverse_matches_individual_inverses() {
+        let values: Vec<ModInt<Q>> = (1..10).map(ModInt::<Q>::new).collect();
+        let batched = ModInt::<Q>::batch_inverse(&values);
+        for (v, v_inv) in values.iter().zip(batched.iter()) {
+            assert_eq!(v_inv.value(), v.inv().value());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn batch_inverse_rejects_a_zero_element() {
+        let values = vec![ModInt::<Q>::new(3), ModInt::<Q>::new(0), ModInt::<Q>::new(5)];
+        ModInt::<Q>::batch_inverse(&values);
+    }
+
+    #[test]
+    fn mod_ring_add_sub_mul_wrap_around_modulus() {
+        let ring = ModRing::new(Q);
+        assert_eq!(ring.add(90, 20), (90 + 20) % Q);
+        assert_eq!(ring.sub(20, 90), (20 + Q - 90) % Q);
+        assert_eq!(ring.mul(90, 20), (90 * 20) % Q);
+    }
+
+    #[test]
+    fn mod_ring_pow_matches_repeated_multiplication() {
+        let ring = ModRing::new(Q);
+        let mut expected = 1u64;
+        for _ in 0..13 {
+            expected = ring.mul(expected, 5);
+        }
+        assert_eq!(ring.pow(5, 13), expected);
+    }
+
+    #[test]
+    fn mod_ring_inv_is_a_true_multiplicative_inverse() {
+        let ring = ModRing::new(Q);
+        for v in 1..Q {
+            assert_eq!(ring.mul(ring.inv(v), v), 1, "failed for {v}");
+        }
+    }
+
+    #[test]
+    fn mod_ring_matches_mod_int_for_the_same_modulus() {
+        let ring = ModRing::new(Q);
+        for v in 1..Q {
+            let x = ModInt::<Q>::new(v);
+            assert_eq!(ring.inv(v), x.inv().value());
+            assert_eq!(ring.pow(v, 7), x.pow(7).value());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no multiplicative inverse")]
+    fn mod_ring_inv_rejects_zero() {
+        ModRing::new(Q).inv(0);
+    }
+}
@@ -0,0 +1,12 @@
+//! Core abstractions for polynomial arithmetic accelerated via TPU/NPU-style
+//! matrix multiplication.
+
+pub mod field;
+pub mod ntt;
+pub mod polynomial;
+pub mod rns;
+
+pub use field::{ModInt, ModRing};
+pub use ntt::NttContext;
+pub use polynomial::{multiply_batch_with_device, Polynomial};
+pub use rns::{ResidueChannel, RnsContext};
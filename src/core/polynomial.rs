@@ -0,0 +1,293 @@
+//! Shared polynomial representation used across the `core` subsystem.
+
+use candle_core::{Device, Result as CandleResult, Tensor};
+
+/// Polynomial with non-negative integer coefficients, lowest degree first.
+#[derive(Debug, Clone)]
+pub struct Polynomial {
+    pub coefficients: Vec<u64>,
+}
+
+impl Polynomial {
+    /// Create a new polynomial from coefficients (lowest degree first).
+    pub fn new(coefficients: Vec<u64>) -> Self {
+        Polynomial { coefficients }
+    }
+
+    /// Naive O(n^2) polynomial multiplication, used as a correctness oracle.
+    pub fn multiply_naive(&self, other: &Polynomial) -> Polynomial {
+        let n = self.coefficients.len();
+        let m = other.coefficients.len();
+        let mut result = vec![0u64; n + m - 1];
+
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            for (j, &b) in other.coefficients.iter().enumerate() {
+                result[i + j] += a * b;
+            }
+        }
+
+        Polynomial::new(result)
+    }
+
+    /// Convert polynomial to Toeplitz matrix representation for multiplication.
+    /// Matrix M is Toeplitz where M_{i,j} = a_{i-j} for i >= j, 0 otherwise.
+    /// When multiplied by vector B, gives A x B.
+    pub fn to_multiplication_matrix(&self, result_degree: usize) -> Vec<Vec<u64>> {
+        let n = self.coefficients.len();
+        let mut matrix = vec![vec![0u64; n]; result_degree];
+
+        for i in 0..result_degree {
+            for j in 0..n {
+                if i >= j && (i - j) < n {
+                    matrix[i][j] = self.coefficients[i - j];
+                }
+            }
+        }
+
+        matrix
+    }
+
+    /// Multiply using matrix multiplication via candle with the provided device.
+    ///
+    /// Coefficients are cast straight to `f32`, so this is only exact while
+    /// every partial sum stays below 2^24; callers working with coefficients
+    /// or degrees large enough to exceed that bound should go through
+    /// [`crate::core::rns::RnsContext`] instead.
+    pub fn multiply_matrix_with_device(
+        &self,
+        other: &Polynomial,
+        device: &Device,
+    ) -> CandleResult<Polynomial> {
+        let n = self.coefficients.len();
+        let m = other.coefficients.len();
+        let result_degree = n + m - 1;
+
+        // Build multiplication matrix (Toeplitz: result_degree x n)
+        let matrix = self.to_multiplication_matrix(result_degree);
+        let matrix_flat: Vec<f32> = matrix
+            .iter()
+            .flat_map(|row| row.iter().map(|&x| x as f32))
+            .collect();
+
+        let matrix_tensor = Tensor::from_vec(matrix_flat, (result_degree, n), device)?;
+
+        // Convert other polynomial to vector (n x 1) - need to pad
+        let mut vec_b = vec![0.0f32; n];
+        for (i, &coeff) in other.coefficients.iter().enumerate() {
+            if i < n {
+                vec_b[i] = coeff as f32;
+            }
+        }
+        let b_tensor = Tensor::from_vec(vec_b, (n, 1), device)?;
+
+        // Perform matrix multiplication
+        let result_tensor = matrix_tensor.matmul(&b_tensor)?;
+
+        // Extract result back to polynomial (reshape from 2D to 1D)
+        let result_reshaped = result_tensor.reshape((result_degree,))?;
+        let result_vec = result_reshaped.to_vec1::<f32>()?;
+        let coefficients: Vec<u64> = result_vec.iter().map(|&x| x as u64).collect();
+
+        Ok(Polynomial::new(coefficients))
+    }
+
+    /// Compare if two polynomials are approximately equal.
+    pub fn approx_eq(&self, other: &Polynomial, epsilon: f64) -> bool {
+        let max_len = self.coefficients.len().max(other.coefficients.len());
+        for i in 0..max_len {
+            let a = self.coefficients.get(i).copied().unwrap_or(0);
+            let b = other.coefficients.get(i).copied().unwrap_or(0);
+            if (a as f64 - b as f64).abs() > epsilon {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Build the skew-circulant matrix for negacyclic convolution in the
+    /// quotient ring `Z[x]/(x^n+1)`: `M[k][j] = a_{k-j}` for `k >= j`, and
+    /// `M[k][j] = -a_{k-j+n}` for `k < j` (wrap-around terms are negated
+    /// since `x^n = -1` in the ring).
+    pub fn to_negacyclic_matrix(&self, n: usize) -> Vec<Vec<i64>> {
+        let mut matrix = vec![vec![0i64; n]; n];
+
+        for k in 0..n {
+            for j in 0..n {
+                if k >= j {
+                    let idx = k - j;
+                    if idx < self.coefficients.len() {
+                        matrix[k][j] = self.coefficients[idx] as i64;
+                    }
+                } else {
+                    let idx = k + n - j;
+                    if idx < self.coefficients.len() {
+                        matrix[k][j] = -(self.coefficients[idx] as i64);
+                    }
+                }
+            }
+        }
+
+        matrix
+    }
+
+    /// Multiply in the negacyclic ring `R_q = Z_q[x]/(x^n+1)`, returning a
+    /// degree-`<n` result reduced modulo `q`. Routes through
+    /// [`crate::core::rns`] rather than casting straight to `f32`, since the
+    /// skew-circulant matrix entries and intermediate dot products easily
+    /// exceed the 2^24 range a single f32 matmul can represent exactly.
+    pub fn multiply_negacyclic_with_device(
+        &self,
+        other: &Polynomial,
+        n: usize,
+        q: u64,
+        device: &Device,
+    ) -> CandleResult<Polynomial> {
+        let matrix: Vec<i64> = self.to_negacyclic_matrix(n).into_iter().flatten().collect();
+
+        let mut vec_b = vec![0u64; n];
+        for (i, &coeff) in other.coefficients.iter().enumerate() {
+            if i < n {
+                vec_b[i] = coeff;
+            }
+        }
+
+        let coefficients = crate::core::rns::matmul_signed_mod_q_with_device(
+            &matrix, n, n, &vec_b, q, device,
+        )?;
+
+        Ok(Polynomial::new(coefficients))
+    }
+}
+
+/// Multiply many polynomial pairs in one dispatch instead of paying Metal's
+/// fixed launch overhead per pair: stack the per-pair Toeplitz matrices into
+/// a `(batch, result_degree, n)` tensor and the right-hand sides into
+/// `(batch, n, 1)`, then issue a single batched matmul (candle broadcasts
+/// the leading batch dimension). All polynomials in `batch_a` must share a
+/// degree, and likewise for `batch_b`.
+pub fn multiply_batch_with_device(
+    batch_a: &[Polynomial],
+    batch_b: &[Polynomial],
+    device: &Device,
+) -> CandleResult<Vec<Polynomial>> {
+    assert_eq!(
+        batch_a.len(),
+        batch_b.len(),
+        "batch_a and batch_b must be the same length"
+    );
+    if batch_a.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let n = batch_a[0].coefficients.len();
+    let m = batch_b[0].coefficients.len();
+    let result_degree = n + m - 1;
+    assert!(
+        batch_a.iter().all(|p| p.coefficients.len() == n),
+        "all of batch_a must share a degree"
+    );
+    assert!(
+        batch_b.iter().all(|p| p.coefficients.len() == m),
+        "all of batch_b must share a degree"
+    );
+
+    let batch = batch_a.len();
+    let mut matrix_flat = Vec::with_capacity(batch * result_degree * n);
+    let mut vector_flat = Vec::with_capacity(batch * n);
+    for (a, b) in batch_a.iter().zip(batch_b.iter()) {
+        let matrix = a.to_multiplication_matrix(result_degree);
+        matrix_flat.extend(matrix.iter().flat_map(|row| row.iter().map(|&x| x as f32)));
+
+        let mut vec_b = vec![0.0f32; n];
+        for (i, &coeff) in b.coefficients.iter().enumerate() {
+            if i < n {
+                vec_b[i] = coeff as f32;
+            }
+        }
+        vector_flat.extend(vec_b);
+    }
+
+    let matrix_tensor = Tensor::from_vec(matrix_flat, (batch, result_degree, n), device)?;
+    let vector_tensor = Tensor::from_vec(vector_flat, (batch, n, 1), device)?;
+
+    let result_tensor = matrix_tensor
+        .matmul(&vector_tensor)?
+        .reshape((batch, result_degree))?;
+    let result_rows = result_tensor.to_vec2::<f32>()?;
+
+    Ok(result_rows
+        .into_iter()
+        .map(|row| Polynomial::new(row.into_iter().map(|x| x as u64).collect()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_negacyclic_matrix_negates_wrap_around_terms() {
+        let a = Polynomial::new(vec![1, 2, 3, 4]);
+        let matrix = a.to_negacyclic_matrix(4);
+
+        // Column 0 is just `a` itself (no wrap-around for j = 0).
+        assert_eq!(matrix[0][0], 1);
+        assert_eq!(matrix[1][0], 2);
+        assert_eq!(matrix[2][0], 3);
+        assert_eq!(matrix[3][0], 4);
+
+        // Column 3 wraps every row except the last: M[k][3] = -a_{k-3+4} for k < 3.
+        assert_eq!(matrix[0][3], -2);
+        assert_eq!(matrix[1][3], -3);
+        assert_eq!(matrix[2][3], -4);
+        assert_eq!(matrix[3][3], 1);
+    }
+
+    /// Reference negacyclic convolution (`O(n^2)`, reduces mod `x^n+1` and
+    /// then mod `q`) used as a correctness oracle for the matmul path.
+    fn multiply_negacyclic_naive(a: &Polynomial, b: &Polynomial, n: usize, q: u64) -> Vec<u64> {
+        let mut acc = vec![0i64; n];
+        for (i, &x) in a.coefficients.iter().enumerate().take(n) {
+            for (j, &y) in b.coefficients.iter().enumerate().take(n) {
+                let k = i + j;
+                let term = x as i64 * y as i64;
+                if k < n {
+                    acc[k] += term;
+                } else {
+                    acc[k - n] -= term;
+                }
+            }
+        }
+        acc.into_iter().map(|x| x.rem_euclid(q as i64) as u64).collect()
+    }
+
+    #[test]
+    fn multiply_negacyclic_with_device_matches_naive_reference() {
+        let device = Device::Cpu;
+        let n = 4;
+        let q = 97;
+        let a = Polynomial::new(vec![1, 2, 3, 4]);
+        let b = Polynomial::new(vec![5, 6, 7, 8]);
+
+        let expected = multiply_negacyclic_naive(&a, &b, n, q);
+        let result = a.multiply_negacyclic_with_device(&b, n, q, &device).unwrap();
+
+        assert_eq!(result.coefficients, expected);
+    }
+
+    #[test]
+    fn multiply_negacyclic_with_device_is_exact_for_coefficients_near_q() {
+        let device = Device::Cpu;
+        let n = 4;
+        let q = 97;
+        // Coefficients close to q exercise the same near-p-1 exactness edge
+        // that the f32-casting version got wrong.
+        let a = Polynomial::new(vec![90, 91, 92, 93]);
+        let b = Polynomial::new(vec![94, 95, 96, 80]);
+
+        let expected = multiply_negacyclic_naive(&a, &b, n, q);
+        let result = a.multiply_negacyclic_with_device(&b, n, q, &device).unwrap();
+
+        assert_eq!(result.coefficients, expected);
+    }
+}
@@ -0,0 +1,483 @@
+//! Residue Number System (RNS) decomposition.
+//!
+//! Candle's matmul runs in `f32`, which only represents integers exactly up
+//! to 2^24 — far below what a single u64 polynomial coefficient multiply
+//! needs. Instead of multiplying in one shot, we decompose each coefficient
+//! into its residues modulo a set of coprime machine-word primes, run one
+//! Metal matmul per residue channel (where every partial sum is kept below
+//! 2^24 by tiling), and reconstruct the true result with CRT.
+//!
+//! The ceiling on those primes is tighter than "the sum stays under 2^24":
+//! a *single* product of two residues near `p - 1` must itself be exact, so
+//! `p * p` has to stay under 2^24, i.e. `p < 4096`. [`RnsContext::new`]
+//! enforces this, and [`choose_primes_for_bound`] picks primes under that
+//! ceiling automatically for a given target product.
+
+use crate::core::field::ModRing;
+use crate::core::polynomial::Polynomial;
+use candle_core::{Device, Result as CandleResult, Tensor};
+
+/// The upper bound under which an `f32` accumulator is exactly representable.
+const F32_EXACT_BOUND: u64 = 1 << 24;
+
+/// The largest prime whose square still fits under [`F32_EXACT_BOUND`] — the
+/// real ceiling on an RNS channel prime, since a single product of two
+/// residues near `p - 1` must already be f32-exact before any summation.
+pub const MAX_RNS_PRIME: u64 = 4093;
+
+/// A polynomial's coefficients reduced modulo a single RNS prime.
+#[derive(Debug, Clone)]
+pub struct ResidueChannel {
+    pub prime: u64,
+    pub residues: Vec<u64>,
+}
+
+/// A set of coprime machine-word primes used to represent coefficients
+/// exactly across decompose -> per-channel matmul -> CRT reconstruct.
+pub struct RnsContext {
+    primes: Vec<u64>,
+}
+
+impl RnsContext {
+    /// Build a context over the given coprime primes. Panics if any prime
+    /// is too large for a single residue product to stay f32-exact (see
+    /// [`MAX_RNS_PRIME`]).
+    pub fn new(primes: Vec<u64>) -> Self {
+        assert!(
+            primes
+                .iter()
+                .all(|&p| (p as u128) * (p as u128) < F32_EXACT_BOUND as u128),
+            "RNS primes must satisfy p*p < 2^24 (p <= {}) so a single residue \
+             product stays exactly representable as f32",
+            MAX_RNS_PRIME
+        );
+        RnsContext { primes }
+    }
+
+    pub fn primes(&self) -> &[u64] {
+        &self.primes
+    }
+
+    /// Decompose a polynomial into one residue channel per prime.
+    pub fn decompose(&self, poly: &Polynomial) -> Vec<ResidueChannel> {
+        self.primes
+            .iter()
+            .map(|&p| {
+                let ring = ModRing::new(p);
+                ResidueChannel {
+                    prime: p,
+                    residues: poly
+                        .coefficients
+                        .iter()
+                        .map(|&c| ring.reduce_u64(c))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Multiply two polynomials exactly by running one matmul per residue
+    /// channel on `device` and reconstructing the result via CRT.
+    pub fn multiply_with_device(
+        &self,
+        a: &Polynomial,
+        b: &Polynomial,
+        device: &Device,
+    ) -> CandleResult<Polynomial> {
+        let result_degree = a.coefficients.len() + b.coefficients.len() - 1;
+        let channels_a = self.decompose(a);
+        let channels_b = self.decompose(b);
+
+        let mut channel_results = Vec::with_capacity(self.primes.len());
+        for (ca, cb) in channels_a.iter().zip(channels_b.iter()) {
+            let residues = Self::multiply_channel_with_device(
+                &ca.residues,
+                &cb.residues,
+                ca.prime,
+                result_degree,
+                device,
+            )?;
+            channel_results.push(ResidueChannel {
+                prime: ca.prime,
+                residues,
+            });
+        }
+
+        Ok(self.reconstruct(&channel_results, result_degree))
+    }
+
+    /// Multiply two polynomials exactly, packing the `k` RNS residue
+    /// channels as the batch dimension so each tile is one batched matmul
+    /// across all channels instead of `k` separate dispatches.
+    pub fn multiply_batch_with_device(
+        &self,
+        a: &Polynomial,
+        b: &Polynomial,
+        device: &Device,
+    ) -> CandleResult<Polynomial> {
+        let n = a.coefficients.len();
+        let m = b.coefficients.len();
+        let result_degree = n + m - 1;
+        let channels_a = self.decompose(a);
+        let channels_b = self.decompose(b);
+        let k = self.primes.len();
+
+        // One tile width must serve every channel in the batch, so size it
+        // for the most restrictive (largest) prime.
+        let tile = self
+            .primes
+            .iter()
+            .map(|&p| (F32_EXACT_BOUND / (p * p).max(1)).max(1))
+            .min()
+            .unwrap_or(1) as usize;
+
+        let mut acc = vec![vec![0f32; result_degree]; k];
+        let mut start = 0;
+        while start < m {
+            let end = (start + tile).min(m);
+            let width = end - start;
+
+            let mut matrix_flat = Vec::with_capacity(k * result_degree * width);
+            let mut vector_flat = Vec::with_capacity(k * width);
+            for (ca, cb) in channels_a.iter().zip(channels_b.iter()) {
+                let mut matrix = vec![0f32; result_degree * width];
+                for i in 0..result_degree {
+                    for (col, j) in (start..end).enumerate() {
+                        if i >= j && (i - j) < n {
+                            matrix[i * width + col] = ca.residues[i - j] as f32;
+                        }
+                    }
+                }
+                matrix_flat.extend(matrix);
+                vector_flat.extend(cb.residues[start..end].iter().map(|&x| x as f32));
+            }
+
+            let matrix_tensor =
+                Tensor::from_vec(matrix_flat, (k, result_degree, width), device)?;
+            let vector_tensor = Tensor::from_vec(vector_flat, (k, width, 1), device)?;
+
+            let tile_result = matrix_tensor
+                .matmul(&vector_tensor)?
+                .reshape((k, result_degree))?
+                .to_vec2::<f32>()?;
+
+            for (channel, row) in tile_result.iter().enumerate() {
+                let p_f = self.primes[channel] as f32;
+                for (acc_i, tile_i) in acc[channel].iter_mut().zip(row.iter()) {
+                    let t = *acc_i + *tile_i;
+                    *acc_i = t - p_f * (t / p_f).floor();
+                }
+            }
+
+            start = end;
+        }
+
+        let channel_results: Vec<ResidueChannel> = self
+            .primes
+            .iter()
+            .zip(acc.iter())
+            .map(|(&p, residues)| {
+                let ring = ModRing::new(p);
+                ResidueChannel {
+                    prime: p,
+                    residues: residues.iter().map(|&x| ring.reduce_u64(x.round() as u64)).collect(),
+                }
+            })
+            .collect();
+
+        Ok(self.reconstruct(&channel_results, result_degree))
+    }
+
+    /// Multiply a single residue channel via a Toeplitz matmul.
+    fn multiply_channel_with_device(
+        a: &[u64],
+        b: &[u64],
+        p: u64,
+        result_degree: usize,
+        device: &Device,
+    ) -> CandleResult<Vec<u64>> {
+        let n = a.len();
+        let m = b.len();
+        let mut matrix_flat = vec![0u64; result_degree * m];
+        for i in 0..result_degree {
+            for j in 0..m {
+                if i >= j && (i - j) < n {
+                    matrix_flat[i * m + j] = a[i - j];
+                }
+            }
+        }
+
+        dense_matvec_mod_with_device(&matrix_flat, result_degree, m, b, p, device)
+    }
+
+    /// Reconstruct coefficients from their residue channels via CRT:
+    /// with `P = prod p_i` and `M_i = P / p_i`,
+    /// `x = (sum_i r_i * M_i * (M_i^-1 mod p_i)) mod P`. Assumes every true
+    /// value is already non-negative and below `P`.
+    pub fn reconstruct(&self, channels: &[ResidueChannel], result_degree: usize) -> Polynomial {
+        let raw = self.reconstruct_raw(channels, result_degree);
+        Polynomial::new(raw.into_iter().map(|x| x as u64).collect())
+    }
+
+    /// Reconstruct values from their residue channels via CRT, then treat
+    /// the `[0, P)` representative as a signed value centered on zero before
+    /// reducing modulo `q`. Needed when channel residues were produced by
+    /// reducing a possibly-negative true value (e.g. negacyclic convolution,
+    /// whose wrap-around terms are negated): plain [`Self::reconstruct`]
+    /// would instead return `P + true_value`, which is not congruent to
+    /// `true_value` mod an arbitrary `q`. Callers must choose primes whose
+    /// product `P` exceeds twice the largest possible absolute value.
+    pub fn reconstruct_signed_mod_q(
+        &self,
+        channels: &[ResidueChannel],
+        result_degree: usize,
+        q: u64,
+    ) -> Vec<u64> {
+        let big_p = self.product();
+        let half = big_p / 2;
+
+        self.reconstruct_raw(channels, result_degree)
+            .into_iter()
+            .map(|x| {
+                let signed = if x > half {
+                    x as i128 - big_p as i128
+                } else {
+                    x as i128
+                };
+                signed.rem_euclid(q as i128) as u64
+            })
+            .collect()
+    }
+
+    fn product(&self) -> u128 {
+        self.primes.iter().map(|&p| p as u128).product()
+    }
+
+    /// Reconstruct the `[0, P)` CRT representative of each position's true
+    /// value, without any further interpretation.
+    fn reconstruct_raw(&self, channels: &[ResidueChannel], result_degree: usize) -> Vec<u128> {
+        let big_p = self.product();
+        let rings: Vec<ModRing> = channels.iter().map(|c| ModRing::new(c.prime)).collect();
+
+        let mut raw = vec![0u128; result_degree];
+        for (k, value) in raw.iter_mut().enumerate() {
+            let mut acc: u128 = 0;
+            for (channel, ring) in channels.iter().zip(rings.iter()) {
+                let p = channel.prime as u128;
+                let m_i = big_p / p;
+                let m_i_inv = ring.inv((m_i % p) as u64) as u128;
+                let term = (channel.residues[k] as u128) % big_p * m_i % big_p * m_i_inv % big_p;
+                acc = (acc + term) % big_p;
+            }
+            *value = acc;
+        }
+
+        raw
+    }
+}
+
+/// Pick enough distinct primes under [`MAX_RNS_PRIME`] that their product
+/// exceeds `min_product`, so a CRT reconstruction over them can recover any
+/// value up to that bound exactly. Primes are taken in decreasing order
+/// starting just under the ceiling, to minimize how many channels (and
+/// matmul dispatches) are needed.
+pub fn choose_primes_for_bound(min_product: u128) -> Vec<u64> {
+    let mut primes = Vec::new();
+    let mut product: u128 = 1;
+    let mut candidate = MAX_RNS_PRIME;
+
+    while product <= min_product {
+        assert!(candidate > 1, "ran out of primes under MAX_RNS_PRIME for the requested bound");
+        if is_prime(candidate) {
+            primes.push(candidate);
+            product *= candidate as u128;
+        }
+        candidate -= 1;
+    }
+
+    primes
+}
+
+fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut d = 2;
+    while d * d <= n {
+        if n % d == 0 {
+            return false;
+        }
+        d += 1;
+    }
+    true
+}
+
+/// Multiply an arbitrary dense `rows x cols` integer matrix (row-major) by a
+/// length-`cols` vector exactly modulo `q`, by decomposing both operands into
+/// residues over `primes`, running the matmul per channel on `device`, and
+/// reconstructing the true dot products via CRT before the final reduction
+/// mod `q`. Callers must choose `primes` whose product exceeds the largest
+/// possible unreduced dot product (`cols * (q - 1)^2`).
+pub(crate) fn matmul_mod_with_device(
+    matrix: &[u64],
+    rows: usize,
+    cols: usize,
+    vector: &[u64],
+    q: u64,
+    primes: &[u64],
+    device: &Device,
+) -> CandleResult<Vec<u64>> {
+    let mut channel_results = Vec::with_capacity(primes.len());
+    for &p in primes {
+        let ring = ModRing::new(p);
+        let matrix_res: Vec<u64> = matrix.iter().map(|&x| ring.reduce_u64(x)).collect();
+        let vector_res: Vec<u64> = vector.iter().map(|&x| ring.reduce_u64(x)).collect();
+        let residues = dense_matvec_mod_with_device(&matrix_res, rows, cols, &vector_res, p, device)?;
+        channel_results.push(ResidueChannel { prime: p, residues });
+    }
+
+    // Reduce the full u128 CRT value: the true dot product can exceed u64.
+    let reconstructed = RnsContext::new(primes.to_vec()).reconstruct_raw(&channel_results, rows);
+    Ok(reconstructed
+        .into_iter()
+        .map(|x| (x % q as u128) as u64)
+        .collect())
+}
+
+/// Multiply a dense `rows x cols` matrix with possibly-negative entries
+/// (row-major) by a length-`cols` vector of non-negative integers, exactly,
+/// reducing the true (signed) dot products modulo `q` only at the very end.
+/// Primes are chosen automatically so their product covers the full
+/// positive-and-negative range of the unreduced dot products.
+pub(crate) fn matmul_signed_mod_q_with_device(
+    matrix: &[i64],
+    rows: usize,
+    cols: usize,
+    vector: &[u64],
+    q: u64,
+    device: &Device,
+) -> CandleResult<Vec<u64>> {
+    let max_entry = matrix.iter().map(|&x| x.unsigned_abs() as u128).max().unwrap_or(0);
+    let max_vector = vector.iter().map(|&x| x as u128).max().unwrap_or(0);
+    let bound = (cols as u128) * max_entry * max_vector;
+    let primes = choose_primes_for_bound(2 * bound);
+    let rns = RnsContext::new(primes.clone());
+
+    let mut channel_results = Vec::with_capacity(primes.len());
+    for &p in &primes {
+        let ring = ModRing::new(p);
+        let matrix_res: Vec<u64> = matrix.iter().map(|&x| x.rem_euclid(p as i64) as u64).collect();
+        let vector_res: Vec<u64> = vector.iter().map(|&x| ring.reduce_u64(x)).collect();
+        let residues = dense_matvec_mod_with_device(&matrix_res, rows, cols, &vector_res, p, device)?;
+        channel_results.push(ResidueChannel { prime: p, residues });
+    }
+
+    Ok(rns.reconstruct_signed_mod_q(&channel_results, rows, q))
+}
+
+/// Multiply a dense `rows x cols` matrix (row-major, entries `< p`) by a
+/// length-`cols` vector (entries `< p`) modulo a single prime `p`, tiling the
+/// shared inner dimension so the running `f32` partial sum never leaves the
+/// exactly-representable range, folding back below `p` after each tile with
+/// a float modular reduction `t - p*floor(t/p)`.
+fn dense_matvec_mod_with_device(
+    matrix: &[u64],
+    rows: usize,
+    cols: usize,
+    vector: &[u64],
+    p: u64,
+    device: &Device,
+) -> CandleResult<Vec<u64>> {
+    // Entries are < p, so a single product is < p*p; size tiles so the
+    // running sum of up to `tile` such products stays below 2^24.
+    let tile = (F32_EXACT_BOUND / (p * p).max(1)).max(1) as usize;
+
+    let mut acc = vec![0f32; rows];
+    let mut start = 0;
+    while start < cols {
+        let end = (start + tile).min(cols);
+        let width = end - start;
+
+        let mut tile_matrix = vec![0f32; rows * width];
+        for i in 0..rows {
+            for (col, j) in (start..end).enumerate() {
+                tile_matrix[i * width + col] = matrix[i * cols + j] as f32;
+            }
+        }
+        let matrix_tensor = Tensor::from_vec(tile_matrix, (rows, width), device)?;
+
+        let vec_b: Vec<f32> = vector[start..end].iter().map(|&x| x as f32).collect();
+        let b_tensor = Tensor::from_vec(vec_b, (width, 1), device)?;
+
+        let tile_result = matrix_tensor
+            .matmul(&b_tensor)?
+            .reshape((rows,))?
+            .to_vec1::<f32>()?;
+
+        let p_f = p as f32;
+        for (acc_i, tile_i) in acc.iter_mut().zip(tile_result.iter()) {
+            let t = *acc_i + *tile_i;
+            *acc_i = t - p_f * (t / p_f).floor();
+        }
+
+        start = end;
+    }
+
+    let ring = ModRing::new(p);
+    Ok(acc.iter().map(|&x| ring.reduce_u64(x.round() as u64)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choose_primes_for_bound_covers_the_requested_product() {
+        let primes = choose_primes_for_bound(1_000_000_000_000);
+        let product: u128 = primes.iter().map(|&p| p as u128).product();
+        assert!(product > 1_000_000_000_000);
+        for &p in &primes {
+            assert!(p * p < F32_EXACT_BOUND, "prime {p} violates the f32-exactness ceiling");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "p*p < 2^24")]
+    fn rns_context_rejects_primes_above_the_exactness_ceiling() {
+        // This is exactly the prime size that silently produced garbage
+        // before the ceiling was enforced.
+        RnsContext::new(vec![1_000_003, 1_000_033]);
+    }
+
+    #[test]
+    fn multiply_with_device_is_exact_for_residues_near_p_minus_one() {
+        let device = Device::Cpu;
+        // Degree-3 polynomials with coefficients deliberately close to the
+        // two largest chosen primes (4093 and 4091), so the residues in those
+        // channels are near `p - 1` -- the case that
+        // silently broke before the ceiling fix forced safe prime sizes.
+        let a = Polynomial::new(vec![4090, 4089, 4088]);
+        let b = Polynomial::new(vec![4087, 4086, 4085]);
+        let expected = a.multiply_naive(&b);
+
+        let primes = choose_primes_for_bound(3u128 * 4090 * 4090);
+        let rns = RnsContext::new(primes);
+        let result = rns.multiply_with_device(&a, &b, &device).unwrap();
+
+        assert_eq!(result.coefficients, expected.coefficients);
+    }
+
+    #[test]
+    fn multiply_batch_with_device_matches_looped_multiply() {
+        let device = Device::Cpu;
+        let a = Polynomial::new(vec![4090, 4089, 4088]);
+        let b = Polynomial::new(vec![4087, 4086, 4085]);
+
+        let primes = choose_primes_for_bound(3u128 * 4090 * 4090);
+        let rns = RnsContext::new(primes);
+        let looped = rns.multiply_with_device(&a, &b, &device).unwrap();
+        let batched = rns.multiply_batch_with_device(&a, &b, &device).unwrap();
+
+        assert_eq!(looped.coefficients, batched.coefficients);
+    }
+}
@@ -4,7 +4,16 @@
 //! (arXiv:2307.06554) for efficient polynomial multiplication using TPU/NPU-inspired techniques.
 //!
 //! # Core Design
-//! Nothing here yet!
+//! Coefficients are multiplied via dense matmul on a candle `Device` (e.g. Metal),
+//! which only accumulates exactly up to 2^24 in `f32`. [`core::rns`] decomposes
+//! each coefficient into residues modulo a set of coprime primes under
+//! `core::rns::MAX_RNS_PRIME` (4093, so that a single residue product still
+//! fits exactly in 2^24) so every channel's matmul stays within that exact
+//! range, then reconstructs the true result via CRT. Reducing non-negative
+//! coefficients into residues, the CRT inverses, and [`core::ntt`]'s
+//! transform matrices use [`core::field::ModRing`]; negative matrix entries,
+//! the CRT sum over the full prime product (which can exceed `u64`), and the
+//! final reduction mod `q` use plain `i64`/`i128`/`u128` remainders.
 
 // Core abstractions
 pub mod core;